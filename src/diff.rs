@@ -0,0 +1,200 @@
+// src/diff.rs
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow2::{
+    array::{Array, BooleanArray, Float64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+    io::parquet::read::{infer_schema, read_metadata, FileReader},
+    io::parquet::write::{
+        CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+    },
+};
+
+use crate::validate;
+use crate::PoolInfoV3;
+
+/// Thresholds controlling what counts as a meaningful change between runs.
+#[derive(Clone, Copy)]
+pub struct DiffConfig {
+    /// Absolute price move that marks a pool as updated.
+    pub price_eps: f64,
+    /// Relative TVL move (fraction, e.g. `0.01` = 1%) that marks a pool as updated.
+    pub tvl_rel: f64,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            price_eps: 1e-9,
+            tvl_rel: 0.01,
+        }
+    }
+}
+
+/// Result of comparing a fresh fetch against the previous Parquet snapshot.
+pub struct Delta<'a> {
+    /// Pools that are new or whose price/TVL moved, paired with their kind.
+    pub rows: Vec<(&'a PoolInfoV3, &'static str)>,
+    /// Ids present last run but absent now.
+    pub removed: Vec<String>,
+}
+
+/// Load `(price, tvl)` keyed by pool `id` from a previous `pools.parquet`.
+///
+/// Returns an empty map when the file does not exist yet (first run).
+pub fn load_previous(path: &str) -> Result<HashMap<String, (f64, f64)>> {
+    let mut map = HashMap::new();
+    let mut file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(map),
+        Err(e) => return Err(e.into()),
+    };
+
+    let metadata = read_metadata(&mut file)?;
+    let schema = infer_schema(&metadata)?;
+    // Keep only the columns we need to rebuild the id -> (price, tvl) index.
+    let schema = schema.filter(|_, f| matches!(f.name.as_str(), "id" | "price" | "tvl"));
+
+    let reader = FileReader::new(file, metadata.row_groups, schema, None, None, None);
+    for chunk in reader {
+        let chunk = chunk?;
+        let ids = chunk.arrays()[0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("id column is Utf8");
+        let prices = chunk.arrays()[1]
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .expect("price column is Float64");
+        let tvls = chunk.arrays()[2]
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .expect("tvl column is Float64");
+        for i in 0..chunk.len() {
+            if let Some(id) = ids.get(i) {
+                let price = prices.get(i).unwrap_or(0.0);
+                let tvl = tvls.get(i).unwrap_or(0.0);
+                map.insert(id.to_string(), (price, tvl));
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Categorise `all` against `previous` into added/updated/removed.
+pub fn compute<'a>(
+    all: &'a [PoolInfoV3],
+    previous: &HashMap<String, (f64, f64)>,
+    cfg: DiffConfig,
+) -> Delta<'a> {
+    let mut rows = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for p in all {
+        seen.insert(p.id.as_str());
+        match previous.get(&p.id) {
+            None => rows.push((p, "added")),
+            Some(&(old_price, old_tvl)) => {
+                let price_moved = (p.price - old_price).abs() > cfg.price_eps;
+                let tvl_moved = if old_tvl.abs() > f64::EPSILON {
+                    ((p.tvl - old_tvl).abs() / old_tvl.abs()) > cfg.tvl_rel
+                } else {
+                    (p.tvl - old_tvl).abs() > f64::EPSILON
+                };
+                if price_moved || tvl_moved {
+                    rows.push((p, "updated"));
+                }
+            }
+        }
+    }
+    let removed = previous
+        .keys()
+        .filter(|id| !seen.contains(id.as_str()))
+        .cloned()
+        .collect();
+    Delta { rows, removed }
+}
+
+/// Write the added/updated rows to `path` with an extra `change_kind` column,
+/// and emit the removed ids (one per line) to `removed_path`.
+pub fn write_delta(
+    delta: &Delta<'_>,
+    token_map: &HashMap<String, String>,
+    path: &str,
+    removed_path: &str,
+) -> Result<()> {
+    let symbol = |addr: &str| {
+        token_map
+            .get(addr)
+            .map(|s| s.as_str())
+            .unwrap_or("UNKNOWN")
+    };
+
+    let ids: Vec<&str> = delta.rows.iter().map(|(p, _)| p.id.as_str()).collect();
+    let progs: Vec<&str> = delta.rows.iter().map(|(p, _)| p.program_id.as_str()).collect();
+    let prices: Vec<f64> = delta.rows.iter().map(|(p, _)| p.price).collect();
+    let tvls: Vec<f64> = delta.rows.iter().map(|(p, _)| p.tvl).collect();
+    let coin_mints: Vec<&str> = delta.rows.iter().map(|(p, _)| p.mint_a.address.as_str()).collect();
+    let pc_mints: Vec<&str> = delta.rows.iter().map(|(p, _)| p.mint_b.address.as_str()).collect();
+    let symbols_a: Vec<&str> = delta.rows.iter().map(|(p, _)| symbol(&p.mint_a.address)).collect();
+    let symbols_b: Vec<&str> = delta.rows.iter().map(|(p, _)| symbol(&p.mint_b.address)).collect();
+    // Keep the delta schema in step with the main/partitioned writers, which
+    // both carry an `is_valid` column.
+    let valids: Vec<bool> = delta.rows.iter().map(|(p, _)| validate::is_valid(p)).collect();
+    let kinds: Vec<&str> = delta.rows.iter().map(|(_, k)| *k).collect();
+
+    let schema = Schema::from(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("program_id", DataType::Utf8, false),
+        Field::new("price", DataType::Float64, false),
+        Field::new("tvl", DataType::Float64, false),
+        Field::new("coin_mint", DataType::Utf8, false),
+        Field::new("pc_mint", DataType::Utf8, false),
+        Field::new("symbol_a", DataType::Utf8, false),
+        Field::new("symbol_b", DataType::Utf8, false),
+        Field::new("is_valid", DataType::Boolean, false),
+        Field::new("change_kind", DataType::Utf8, false),
+    ]);
+
+    let chunk: Chunk<Arc<dyn Array>> = Chunk::new(vec![
+        Arc::new(Utf8Array::<i32>::from_slice(&ids)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&progs)) as _,
+        Arc::new(Float64Array::from_slice(&prices)) as _,
+        Arc::new(Float64Array::from_slice(&tvls)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&coin_mints)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&pc_mints)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&symbols_a)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&symbols_b)) as _,
+        Arc::new(BooleanArray::from_slice(&valids)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&kinds)) as _,
+    ]);
+
+    let mut file = File::create(path)?;
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Snappy,
+        version: Version::V2,
+        data_pagesize_limit: Some(1024 * 1024),
+    };
+    let mut writer = FileWriter::try_new(&mut file, schema.clone(), options)?;
+    let encodings: Vec<Vec<Encoding>> = (0..schema.fields.len())
+        .map(|_| vec![Encoding::Plain])
+        .collect();
+    let row_groups =
+        RowGroupIterator::try_new(std::iter::once(Ok(chunk)), &schema, options, encodings)?;
+    for group in row_groups {
+        writer.write(group?)?;
+    }
+    writer.end(None)?;
+
+    let mut removed = File::create(removed_path)?;
+    for id in &delta.removed {
+        writeln!(removed, "{id}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,278 @@
+// src/geyser_source.rs
+use std::collections::HashMap;
+use std::pin::Pin;
+
+use anyhow::Result;
+use futures::{SinkExt, Stream, StreamExt};
+
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestFilterAccounts,
+};
+
+use crate::{MintInfo, PoolInfoV3};
+
+/// Raydium AMM v4 program whose pool accounts we subscribe to.
+const RAYDIUM_AMM_PROGRAM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+/// Subscribe to Raydium AMM account updates over Yellowstone gRPC and yield
+/// `PoolInfoV3`-shaped records, mirroring [`crate::all_pools`] so the rest of
+/// the pipeline (Parquet / Postgres / WS) is unchanged.
+///
+/// `AmmInfo` itself does not hold live reserves — those live in the pool's
+/// coin/pc SPL token vault accounts. So this subscribes in two stages: AMM
+/// account updates are decoded for their mints and vault addresses, and the
+/// moment a pool's vaults are known we extend the live subscription to also
+/// track those vault accounts. A pool is only emitted once both legs' vault
+/// balances have been observed at least once.
+pub fn all_pools_grpc(
+    endpoint: impl Into<String>,
+    x_token: Option<String>,
+) -> Pin<Box<dyn Stream<Item = Result<Vec<PoolInfoV3>>> + Send>> {
+    let endpoint = endpoint.into();
+
+    Box::pin(async_stream::try_stream! {
+        let mut client = GeyserGrpcClient::build_from_shared(endpoint)?
+            .x_token(x_token)?
+            .connect()
+            .await?;
+
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "raydium".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![RAYDIUM_AMM_PROGRAM.to_string()],
+                ..Default::default()
+            },
+        );
+        let request = SubscribeRequest {
+            accounts: accounts.clone(),
+            ..Default::default()
+        };
+
+        let (mut sink, mut stream) = client.subscribe_with_request(Some(request)).await?;
+
+        // Pools seen so far, keyed by the AMM account's own pubkey.
+        let mut pools: HashMap<String, PendingPool> = HashMap::new();
+        // vault pubkey (base58) -> (pool id, which leg it funds).
+        let mut vaults: HashMap<String, (String, Leg)> = HashMap::new();
+
+        while let Some(update) = stream.next().await {
+            let update = update?;
+            let Some(UpdateOneof::Account(account)) = update.update_oneof else {
+                continue;
+            };
+            let Some(info) = account.account else {
+                continue;
+            };
+            let pubkey = bs58_encode(&info.pubkey);
+
+            if let Some((pool_id, leg)) = vaults.get(&pubkey) {
+                let Some(balance) = read_token_amount(&info.data) else {
+                    continue;
+                };
+                if let Some(pending) = pools.get_mut(pool_id) {
+                    pending.set_reserve(*leg, balance);
+                    if let Some(pool) = pending.to_pool_info() {
+                        yield vec![pool];
+                    }
+                }
+                continue;
+            }
+
+            let Some(state) = AmmState::unpack(&info.data) else {
+                continue;
+            };
+            let pool_id = bs58_encode(&info.pubkey);
+            let coin_vault = bs58_encode(&state.coin_vault);
+            let pc_vault = bs58_encode(&state.pc_vault);
+
+            let mut newly_tracked = Vec::new();
+            if vaults.insert(coin_vault.clone(), (pool_id.clone(), Leg::Coin)).is_none() {
+                newly_tracked.push(coin_vault);
+            }
+            if vaults.insert(pc_vault.clone(), (pool_id.clone(), Leg::Pc)).is_none() {
+                newly_tracked.push(pc_vault);
+            }
+
+            pools
+                .entry(pool_id.clone())
+                .or_insert_with(|| PendingPool::new(pool_id.clone(), bs58_encode(&info.owner)))
+                .set_mints(&state);
+
+            if !newly_tracked.is_empty() {
+                accounts
+                    .entry("raydium_vaults".to_string())
+                    .or_default()
+                    .account
+                    .extend(newly_tracked);
+                sink.send(SubscribeRequest {
+                    accounts: accounts.clone(),
+                    ..Default::default()
+                })
+                .await?;
+            }
+        }
+    })
+}
+
+/// A pool whose mints are known but whose reserves may still be incomplete.
+struct PendingPool {
+    id: String,
+    program_id: String,
+    coin_mint: Option<[u8; 32]>,
+    pc_mint: Option<[u8; 32]>,
+    coin_decimals: u32,
+    pc_decimals: u32,
+    coin_reserve: Option<u64>,
+    pc_reserve: Option<u64>,
+}
+
+#[derive(Clone, Copy)]
+enum Leg {
+    Coin,
+    Pc,
+}
+
+impl PendingPool {
+    fn new(id: String, program_id: String) -> Self {
+        Self {
+            id,
+            program_id,
+            coin_mint: None,
+            pc_mint: None,
+            coin_decimals: 0,
+            pc_decimals: 0,
+            coin_reserve: None,
+            pc_reserve: None,
+        }
+    }
+
+    fn set_mints(&mut self, state: &AmmState) {
+        self.coin_mint = Some(state.coin_mint);
+        self.pc_mint = Some(state.pc_mint);
+        self.coin_decimals = state.coin_decimals;
+        self.pc_decimals = state.pc_decimals;
+    }
+
+    fn set_reserve(&mut self, leg: Leg, amount: u64) {
+        match leg {
+            Leg::Coin => self.coin_reserve = Some(amount),
+            Leg::Pc => self.pc_reserve = Some(amount),
+        }
+    }
+
+    /// Builds a `PoolInfoV3` once both legs' vault balances have been seen.
+    fn to_pool_info(&self) -> Option<PoolInfoV3> {
+        let coin_mint = self.coin_mint?;
+        let pc_mint = self.pc_mint?;
+        let coin_reserve = self.coin_reserve?;
+        let pc_reserve = self.pc_reserve?;
+
+        // Vault balances are raw atomic units; scale each leg to whole
+        // tokens by its own mint decimals before deriving price/TVL, the
+        // same normalization the REST path's API response already applies,
+        // so the two sources stay comparable (same `DiffConfig` thresholds,
+        // same WS/Parquet columns).
+        let coin_amount = coin_reserve as f64 / 10f64.powi(self.coin_decimals as i32);
+        let pc_amount = pc_reserve as f64 / 10f64.powi(self.pc_decimals as i32);
+
+        let price = if coin_amount > 0.0 { pc_amount / coin_amount } else { 0.0 };
+        // TVL approximated as both legs valued in the pc mint.
+        let tvl = pc_amount + coin_amount * price;
+
+        Some(PoolInfoV3 {
+            id: self.id.clone(),
+            price,
+            tvl,
+            program_id: self.program_id.clone(),
+            mint_a: mint(&coin_mint),
+            mint_b: mint(&pc_mint),
+        })
+    }
+}
+
+/// Minimal mint placeholder built from an on-chain address; the symbol is
+/// resolved later from the token map, as it is for the REST path.
+fn mint(address: &[u8; 32]) -> MintInfo {
+    MintInfo {
+        chain_id: 101,
+        address: bs58_encode(address),
+        program_id: String::new(),
+        logo_uri: String::new(),
+        symbol: String::new(),
+        name: String::new(),
+        decimals: 0,
+        tags: Vec::new(),
+        extensions: serde_json::Value::Null,
+    }
+}
+
+/// The subset of the AMM account layout we need: the two mints and the two
+/// vault addresses that actually hold the live reserves.
+struct AmmState {
+    coin_mint: [u8; 32],
+    pc_mint: [u8; 32],
+    coin_vault: [u8; 32],
+    pc_vault: [u8; 32],
+    coin_decimals: u32,
+    pc_decimals: u32,
+}
+
+impl AmmState {
+    // Offsets follow the Raydium AMM v4 `AmmInfo` layout: coin_decimals and
+    // pc_decimals are the 5th/6th u64 in the header, followed by the
+    // fixed-point fees/state block, then coin_vault, pc_vault, coin_mint,
+    // pc_mint, lp_mint, ... as Pubkeys. Reserves are deliberately NOT read
+    // from this account: the coin/pc amounts here are historical
+    // accounting fields, not the live vault balances.
+    const COIN_DECIMALS_OFFSET: usize = 32;
+    const PC_DECIMALS_OFFSET: usize = 40;
+    const COIN_VAULT_OFFSET: usize = 336;
+    const PC_VAULT_OFFSET: usize = 368;
+    const COIN_MINT_OFFSET: usize = 400;
+    const PC_MINT_OFFSET: usize = 432;
+    const MIN_LEN: usize = 464;
+
+    fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::MIN_LEN {
+            return None;
+        }
+        Some(Self {
+            coin_vault: read_pubkey(data, Self::COIN_VAULT_OFFSET),
+            pc_vault: read_pubkey(data, Self::PC_VAULT_OFFSET),
+            coin_mint: read_pubkey(data, Self::COIN_MINT_OFFSET),
+            pc_mint: read_pubkey(data, Self::PC_MINT_OFFSET),
+            coin_decimals: read_u64(data, Self::COIN_DECIMALS_OFFSET) as u32,
+            pc_decimals: read_u64(data, Self::PC_DECIMALS_OFFSET) as u32,
+        })
+    }
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(&data[offset..offset + 32]);
+    buf
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
+
+/// Reads the `amount` field of an SPL token account: a fixed 165-byte layout
+/// of `mint(32) | owner(32) | amount(8) | ...`, so the balance always sits at
+/// byte offset 64 regardless of mint or owner.
+fn read_token_amount(data: &[u8]) -> Option<u64> {
+    if data.len() < 72 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[64..72]);
+    Some(u64::from_le_bytes(buf))
+}
+
+fn bs58_encode(bytes: &[u8]) -> String {
+    bs58::encode(bytes).into_string()
+}
@@ -6,8 +6,16 @@ use futures::{Stream, StreamExt};
 use reqwest::Client;
 use serde::Deserialize;
 
+mod diff;
+mod geyser_source;
+mod metrics;
+mod partition;
+mod postgres;
+mod validate;
+mod ws_server;
+
 use arrow2::{
-    array::{Float64Array, Utf8Array},
+    array::{BooleanArray, Float64Array, Utf8Array},
     chunk::Chunk,
     datatypes::{DataType, Field, Schema},
     io::parquet::write::{
@@ -24,9 +32,13 @@ struct ApiPage<T> {
 #[derive(Deserialize)]
 struct PageData<T> {
     data: Vec<T>,
-    hasNextPage: bool,
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
 }
 
+// Mirrors the full API shape even though only `address` is read today, so
+// deserialization keeps working if/when the other fields are needed.
+#[allow(dead_code)]
 #[derive(Deserialize, Clone)]
 struct MintInfo {
     #[serde(rename = "chainId")]   chain_id: u64,
@@ -54,7 +66,7 @@ struct PoolInfoV3 {
     mint_b: MintInfo,
 }
 
-/// Returns a Stream yielding each page's Vec<PoolInfoV3>, stopping when hasNextPage=false.
+/// Returns a Stream yielding each page's Vec<PoolInfoV3>, stopping when has_next_page=false.
 fn all_pools(
     base_url: impl Into<String>,
 ) -> Pin<Box<dyn Stream<Item = Result<Vec<PoolInfoV3>>> + Send>> {
@@ -64,7 +76,8 @@ fn all_pools(
     Box::pin(async_stream::try_stream! {
         let mut page = 1;
         loop {
-            let resp = client
+            let timer = crate::metrics::Timer::start();
+            let http = client
                 .get(&base_url)
                 .query(&[
                 ("poolType", "all".to_string()),
@@ -73,7 +86,9 @@ fn all_pools(
                 ("pageSize", 1000.to_string()),
                 ("page", page.to_string())
             ])
-                .send().await?
+                .send().await?;
+            crate::metrics::metrics().observe_fetch(timer.elapsed_ms());
+            let resp = http
                 .error_for_status()?
                 .json::<ApiPage<PoolInfoV3>>().await?;
 
@@ -85,9 +100,10 @@ fn all_pools(
             if batch.is_empty() {
                 break;
             }
+            crate::metrics::metrics().observe_page(batch.len());
             yield batch.clone();
 
-            if !resp.data.hasNextPage {
+            if !resp.data.has_next_page {
                 break;
             }
             page += 1;
@@ -96,7 +112,12 @@ fn all_pools(
 }
 
 /// Write one big Parquet file with a single row‐group containing all of `all_pools`.
-fn write_pools_parquet(all: &[PoolInfoV3], token_map: &HashMap<String, String>, path: &str) -> Result<()> {
+fn write_pools_parquet(
+    all: &[PoolInfoV3],
+    token_map: &HashMap<String, String>,
+    valid: &[bool],
+    path: &str,
+) -> Result<()> {
     let ids:        Vec<&str> = all.iter().map(|p| p.id.as_str()).collect();
     let progs:      Vec<&str> = all.iter().map(|p| p.program_id.as_str()).collect();
     let prices:     Vec<f64>   = all.iter().map(|p| p.price).collect();
@@ -125,6 +146,7 @@ fn write_pools_parquet(all: &[PoolInfoV3], token_map: &HashMap<String, String>,
     let pc_mint_arr   = Utf8Array::<i32>::from_slice(&pc_mints);
     let symbols_a_arr = Utf8Array::<i32>::from_slice(&symbols_a);
     let symbols_b_arr = Utf8Array::<i32>::from_slice(&symbols_b);
+    let is_valid_arr  = BooleanArray::from_slice(valid);
 
     let schema = Schema::from(vec![
         Field::new("id",         DataType::Utf8,   false),
@@ -135,6 +157,7 @@ fn write_pools_parquet(all: &[PoolInfoV3], token_map: &HashMap<String, String>,
         Field::new("pc_mint", DataType::Utf8,   false),
         Field::new("symbol_a",        DataType::Utf8,   false),
         Field::new("symbol_b",         DataType::Utf8,   false),
+        Field::new("is_valid",        DataType::Boolean, false),
     ]);
 
     let chunk: Chunk<Arc<dyn arrow2::array::Array>> = Chunk::new(vec![
@@ -146,6 +169,7 @@ fn write_pools_parquet(all: &[PoolInfoV3], token_map: &HashMap<String, String>,
         Arc::new(pc_mint_arr)   as _,
         Arc::new(symbols_a_arr)   as _,
         Arc::new(symbols_b_arr)   as _,
+        Arc::new(is_valid_arr)    as _,
     ]);
 
     let mut file = File::create(path)?;
@@ -194,7 +218,60 @@ async fn load_token_map(client: &Client) -> Result<HashMap<String, String>> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let mut pages = all_pools("https://api-v3.raydium.io/pools/info/list");
+    const BASE_URL: &str = "https://api-v3.raydium.io/pools/info/list";
+
+    // Optional Prometheus endpoint; enabled by setting `RAYDIUM_METRICS_ADDR`.
+    if let Ok(addr) = std::env::var("RAYDIUM_METRICS_ADDR") {
+        metrics::serve(addr);
+    }
+
+    // Source: `rest` (default) paginates the HTTP API, `grpc` streams account
+    // updates from a Yellowstone endpoint. Pick with `--source rest|grpc` or
+    // `RAYDIUM_SOURCE`.
+    let source = std::env::args()
+        .skip_while(|a| a != "--source")
+        .nth(1)
+        .or_else(|| std::env::var("RAYDIUM_SOURCE").ok())
+        .unwrap_or_else(|| "rest".to_string());
+    let grpc_endpoint = || {
+        std::env::var("RAYDIUM_GRPC_ENDPOINT").unwrap_or_else(|_| "http://127.0.0.1:10000".to_string())
+    };
+    let grpc_token = || std::env::var("RAYDIUM_GRPC_TOKEN").ok();
+
+    // Server mode keeps polling and streams updates over WebSocket instead of
+    // doing a single one-shot dump. Enable with `--server` or `RAYDIUM_SERVE`.
+    let server_mode = std::env::args().any(|a| a == "--server")
+        || std::env::var("RAYDIUM_SERVE").is_ok();
+    if server_mode {
+        let addr = std::env::var("RAYDIUM_WS_ADDR").unwrap_or_else(|_| "127.0.0.1:9001".to_string());
+        let interval = std::env::var("RAYDIUM_POLL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(std::time::Duration::from_secs)
+            .unwrap_or_else(|| std::time::Duration::from_secs(30));
+        let pool_source = match source.as_str() {
+            "grpc" => ws_server::PoolSource::Grpc {
+                endpoint: grpc_endpoint(),
+                x_token: grpc_token(),
+            },
+            _ => ws_server::PoolSource::Rest {
+                base_url: BASE_URL.to_string(),
+            },
+        };
+        return ws_server::serve(&addr, pool_source, interval).await;
+    }
+
+    // The one-shot path below collects a stream to completion before writing
+    // a single output; the gRPC source never completes, so it only makes
+    // sense wired into the server's push-based broadcast loop above.
+    if source == "grpc" {
+        anyhow::bail!(
+            "--source grpc is a live subscription with no end; run it with --server \
+             (WebSocket broadcast) instead of the one-shot writer"
+        );
+    }
+
+    let mut pages = all_pools(BASE_URL);
     let client    = Client::new();
     let token_map = load_token_map(&client).await?;
     let mut all    = Vec::new();
@@ -204,9 +281,71 @@ async fn main() -> Result<()> {
         all.extend(page);
     }
     println!("total pools = {}", all.len());
+    metrics::metrics().set_total(all.len());
+
+    // Output backend: `parquet` (default) writes `pools.parquet` plus a delta
+    // against the previous one, `partitioned` appends a file under `data/`,
+    // `postgres` upserts into a `pools` table. Pick via `--output <backend>`
+    // or the `RAYDIUM_OUTPUT` env var.
+    let backend = std::env::args()
+        .skip_while(|a| a != "--output")
+        .nth(1)
+        .or_else(|| std::env::var("RAYDIUM_OUTPUT").ok())
+        .unwrap_or_else(|| "parquet".to_string());
+
+    match backend.as_str() {
+        "partitioned" => {
+            let cfg = partition::PartitionConfig {
+                compression: partition::Compression::parse(
+                    &std::env::var("RAYDIUM_COMPRESSION").unwrap_or_default(),
+                ),
+                row_group_size: std::env::var("RAYDIUM_ROW_GROUP")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(50_000)
+                    .max(1),
+            };
+            let valid = validate::validate_pools(&all);
+            let timer = metrics::Timer::start();
+            let path = partition::write_pools_partitioned(&all, &token_map, &valid, "data", cfg)?;
+            metrics::metrics().observe_write(timer.elapsed_ms());
+            println!("✅ Wrote {path}");
+        }
+        "postgres" => {
+            let conn_str = std::env::var("RAYDIUM_PG_CONN")
+                .unwrap_or_else(|_| "host=localhost user=postgres dbname=raydium".to_string());
+            postgres::write_pools_postgres(&all, &token_map, &conn_str).await?;
+            println!("✅ Upserted {} pools into postgres", all.len());
+        }
+        _ => {
+            // Diff against the previous snapshot before clobbering it, so a run
+            // emits a compact delta plus a list of removed pools.
+            let cfg = diff::DiffConfig {
+                price_eps: std::env::var("RAYDIUM_PRICE_EPS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(1e-9),
+                tvl_rel: std::env::var("RAYDIUM_TVL_REL")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0.01),
+            };
+            let previous = diff::load_previous("pools.parquet")?;
+            let delta = diff::compute(&all, &previous, cfg);
+            diff::write_delta(&delta, &token_map, "pools_delta.parquet", "pools_removed.txt")?;
+            println!(
+                "🔎 delta: {} changed/added, {} removed",
+                delta.rows.len(),
+                delta.removed.len()
+            );
 
-    write_pools_parquet(&all, &token_map, "pools.parquet")?;
-    println!("✅ Wrote pools.parquet");
+            let valid = validate::validate_pools(&all);
+            let timer = metrics::Timer::start();
+            write_pools_parquet(&all, &token_map, &valid, "pools.parquet")?;
+            metrics::metrics().observe_write(timer.elapsed_ms());
+            println!("✅ Wrote pools.parquet");
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,180 @@
+// src/metrics.rs
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+/// Upper bounds (inclusive, in milliseconds) for the latency histograms.
+const BUCKETS_MS: [f64; 12] = [
+    1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
+/// A fixed-bucket latency histogram rendered in Prometheus text format.
+///
+/// Bucket counts are cumulative at render time (`le` semantics); the running
+/// `sum` is kept in microseconds so it can live in an `AtomicU64`.
+struct Histogram {
+    counts: [AtomicU64; BUCKETS_MS.len()],
+    inf: AtomicU64,
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        // `AtomicU64::new(0)` is const; build the array explicitly. Each
+        // `Z` below is expanded in place (not shared), so the usual
+        // interior-mutability-const footgun doesn't apply here.
+        #[allow(clippy::declare_interior_mutable_const)]
+        const Z: AtomicU64 = AtomicU64::new(0);
+        Self {
+            counts: [Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z],
+            inf: Z,
+            sum_micros: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, ms: f64) {
+        let mut placed = false;
+        for (i, bound) in BUCKETS_MS.iter().enumerate() {
+            if ms <= *bound {
+                self.counts[i].fetch_add(1, Ordering::Relaxed);
+                placed = true;
+                break;
+            }
+        }
+        if !placed {
+            self.inf.fetch_add(1, Ordering::Relaxed);
+        }
+        self.sum_micros
+            .fetch_add((ms * 1000.0) as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, name: &str, out: &mut String) {
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        let mut cumulative = 0u64;
+        for (i, bound) in BUCKETS_MS.iter().enumerate() {
+            cumulative += self.counts[i].load(Ordering::Relaxed);
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+        cumulative += self.inf.load(Ordering::Relaxed);
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {cumulative}");
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1000.0;
+        let _ = writeln!(out, "{name}_sum {sum}");
+        let _ = writeln!(out, "{name}_count {}", self.count.load(Ordering::Relaxed));
+    }
+}
+
+/// Process-wide metrics for the fetch/write pipeline.
+pub struct Metrics {
+    pools_fetched: AtomicU64,
+    pools_total: AtomicU64,
+    fetch_latency: Histogram,
+    write_latency: Histogram,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            pools_fetched: AtomicU64::new(0),
+            pools_total: AtomicU64::new(0),
+            fetch_latency: Histogram::new(),
+            write_latency: Histogram::new(),
+        }
+    }
+
+    /// Count pools returned by a single page fetch.
+    pub fn observe_page(&self, n: usize) {
+        self.pools_fetched.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    /// Set the current total pool count.
+    pub fn set_total(&self, n: usize) {
+        self.pools_total.store(n as u64, Ordering::Relaxed);
+    }
+
+    /// Record one per-page API latency (milliseconds).
+    pub fn observe_fetch(&self, ms: f64) {
+        self.fetch_latency.observe(ms);
+    }
+
+    /// Record one Parquet-write latency (milliseconds).
+    pub fn observe_write(&self, ms: f64) {
+        self.write_latency.observe(ms);
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# TYPE raydium_pools_fetched counter");
+        let _ = writeln!(
+            out,
+            "raydium_pools_fetched {}",
+            self.pools_fetched.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE raydium_pools_total gauge");
+        let _ = writeln!(
+            out,
+            "raydium_pools_total {}",
+            self.pools_total.load(Ordering::Relaxed)
+        );
+        self.fetch_latency
+            .render("raydium_fetch_latency_ms", &mut out);
+        self.write_latency
+            .render("raydium_write_latency_ms", &mut out);
+        out
+    }
+}
+
+static METRICS: Metrics = Metrics::new();
+
+/// Access the global metrics registry.
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// Spawn a minimal HTTP server exposing `/metrics` in Prometheus text format.
+pub fn serve(addr: String) {
+    tokio::spawn(async move {
+        if let Err(e) = run(addr).await {
+            eprintln!("metrics server error: {e}");
+        }
+    });
+}
+
+async fn run(addr: String) -> Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+    println!("metrics server listening on {addr}");
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let body = metrics().render();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+}
+
+/// A lightweight stopwatch used at call sites to time an operation once.
+pub struct Timer {
+    start: std::time::Instant,
+}
+
+impl Timer {
+    pub fn start() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Elapsed time in milliseconds.
+    pub fn elapsed_ms(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+}
@@ -0,0 +1,190 @@
+// src/partition.rs
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use arrow2::{
+    array::{Array, BooleanArray, Float64Array, Int64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    io::parquet::write::{
+        CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+    },
+};
+
+use crate::PoolInfoV3;
+
+/// Compression codec for the partitioned output, selectable via config.
+#[derive(Clone, Copy)]
+pub enum Compression {
+    Snappy,
+    Zstd,
+}
+
+impl Compression {
+    /// Parse a codec name (case-insensitive); defaults to Snappy on anything else.
+    pub fn parse(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "zstd" => Compression::Zstd,
+            _ => Compression::Snappy,
+        }
+    }
+
+    fn to_options(self) -> CompressionOptions {
+        match self {
+            Compression::Snappy => CompressionOptions::Snappy,
+            Compression::Zstd => CompressionOptions::Zstd(None),
+        }
+    }
+}
+
+/// Tunables for the partitioned writer.
+#[derive(Clone, Copy)]
+pub struct PartitionConfig {
+    pub compression: Compression,
+    /// Rows per Parquet row group; large pool sets are split across several.
+    pub row_group_size: usize,
+}
+
+impl Default for PartitionConfig {
+    fn default() -> Self {
+        Self {
+            compression: Compression::Snappy,
+            row_group_size: 50_000,
+        }
+    }
+}
+
+/// Append a snapshot to a Hive-partitioned tree rooted at `base_dir`.
+///
+/// Files land at `base_dir/date=YYYY-MM-DD/hour=HH/pools-<unixmillis>.parquet`,
+/// chunked into row groups of [`PartitionConfig::row_group_size`] rows, with a
+/// `fetched_at` timestamp column stamping every row. Each run writes a fresh
+/// file, so the layout is append-only and directly queryable by downstream
+/// engines. Returns the path written.
+pub fn write_pools_partitioned(
+    all: &[PoolInfoV3],
+    token_map: &HashMap<String, String>,
+    valid: &[bool],
+    base_dir: &str,
+    cfg: PartitionConfig,
+) -> Result<String> {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let (date, hour) = date_hour(now_ms);
+
+    let dir = format!("{base_dir}/date={date}/hour={hour:02}");
+    fs::create_dir_all(&dir)?;
+    let path = format!("{dir}/pools-{now_ms}.parquet");
+
+    let schema = Schema::from(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("program_id", DataType::Utf8, false),
+        Field::new("price", DataType::Float64, false),
+        Field::new("tvl", DataType::Float64, false),
+        Field::new("coin_mint", DataType::Utf8, false),
+        Field::new("pc_mint", DataType::Utf8, false),
+        Field::new("symbol_a", DataType::Utf8, false),
+        Field::new("symbol_b", DataType::Utf8, false),
+        Field::new("is_valid", DataType::Boolean, false),
+        Field::new(
+            "fetched_at",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            false,
+        ),
+    ]);
+
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: cfg.compression.to_options(),
+        version: Version::V2,
+        data_pagesize_limit: Some(1024 * 1024),
+    };
+    let encodings: Vec<Vec<Encoding>> = (0..schema.fields.len())
+        .map(|_| vec![Encoding::Plain])
+        .collect();
+
+    // One chunk per row group; `RowGroupIterator` turns each into a group.
+    let chunks: Vec<Result<Chunk<Arc<dyn Array>>, arrow2::error::Error>> = all
+        .chunks(cfg.row_group_size)
+        .zip(valid.chunks(cfg.row_group_size))
+        .map(|(rows, valid_rows)| Ok(build_chunk(rows, valid_rows, token_map, now_ms)))
+        .collect();
+
+    let mut file = File::create(&path)?;
+    let mut writer = FileWriter::try_new(&mut file, schema.clone(), options)?;
+    let row_groups = RowGroupIterator::try_new(chunks.into_iter(), &schema, options, encodings)?;
+    for group in row_groups {
+        writer.write(group?)?;
+    }
+    writer.end(None)?;
+
+    Ok(path)
+}
+
+fn build_chunk(
+    rows: &[PoolInfoV3],
+    valid: &[bool],
+    token_map: &HashMap<String, String>,
+    fetched_at: i64,
+) -> Chunk<Arc<dyn Array>> {
+    let symbol = |addr: &str| {
+        token_map
+            .get(addr)
+            .map(|s| s.as_str())
+            .unwrap_or("UNKNOWN")
+    };
+
+    let ids: Vec<&str> = rows.iter().map(|p| p.id.as_str()).collect();
+    let progs: Vec<&str> = rows.iter().map(|p| p.program_id.as_str()).collect();
+    let prices: Vec<f64> = rows.iter().map(|p| p.price).collect();
+    let tvls: Vec<f64> = rows.iter().map(|p| p.tvl).collect();
+    let coin_mints: Vec<&str> = rows.iter().map(|p| p.mint_a.address.as_str()).collect();
+    let pc_mints: Vec<&str> = rows.iter().map(|p| p.mint_b.address.as_str()).collect();
+    let symbols_a: Vec<&str> = rows.iter().map(|p| symbol(&p.mint_a.address)).collect();
+    let symbols_b: Vec<&str> = rows.iter().map(|p| symbol(&p.mint_b.address)).collect();
+    let fetched: Vec<i64> = vec![fetched_at; rows.len()];
+
+    Chunk::new(vec![
+        Arc::new(Utf8Array::<i32>::from_slice(&ids)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&progs)) as _,
+        Arc::new(Float64Array::from_slice(&prices)) as _,
+        Arc::new(Float64Array::from_slice(&tvls)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&coin_mints)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&pc_mints)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&symbols_a)) as _,
+        Arc::new(Utf8Array::<i32>::from_slice(&symbols_b)) as _,
+        Arc::new(BooleanArray::from_slice(valid)) as _,
+        Arc::new(
+            Int64Array::from_slice(&fetched)
+                .to(DataType::Timestamp(TimeUnit::Millisecond, None)),
+        ) as _,
+    ])
+}
+
+/// Convert a unix-millis timestamp to `(YYYY-MM-DD, hour)` in UTC, using the
+/// civil-from-days algorithm so we avoid pulling in a date crate.
+fn date_hour(unix_ms: i64) -> (String, u32) {
+    let secs = unix_ms.div_euclid(1000);
+    let days = secs.div_euclid(86_400);
+    let tod = secs.rem_euclid(86_400);
+    let hour = (tod / 3600) as u32;
+
+    // Howard Hinnant's days-from-civil, run in reverse.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let year = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if month <= 2 { year + 1 } else { year };
+
+    (format!("{year:04}-{month:02}-{day:02}"), hour)
+}
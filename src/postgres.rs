@@ -0,0 +1,110 @@
+// src/postgres.rs
+use std::collections::HashMap;
+
+use anyhow::Result;
+use tokio_postgres::NoTls;
+
+use crate::PoolInfoV3;
+
+/// How many rows we cram into a single multi-VALUES `INSERT` statement.
+const BATCH_ROWS: usize = 500;
+
+/// Upsert every pool snapshot into a Postgres `pools` table.
+///
+/// The table holds the Parquet columns `id`, `program_id`, `price`, `tvl`,
+/// `coin_mint`, `pc_mint`, `symbol_a` and `symbol_b` (not `is_valid`) plus a
+/// `fetched_at` timestamp, and is created on demand. Repeated runs update
+/// `price`, `tvl` and `fetched_at` of existing rows via
+/// `ON CONFLICT (id) DO UPDATE`; the other columns keep their first-inserted
+/// values, and rows of pools missing from a later poll are not deleted.
+pub async fn write_pools_postgres(
+    all: &[PoolInfoV3],
+    token_map: &HashMap<String, String>,
+    conn_str: &str,
+) -> Result<()> {
+    let (client, connection) = tokio_postgres::connect(conn_str, NoTls).await?;
+    // The connection future drives the protocol; it has to be polled for the
+    // client handle to make progress, so park it on its own task.
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("postgres connection error: {e}");
+        }
+    });
+
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS pools (
+                id          TEXT PRIMARY KEY,
+                program_id  TEXT NOT NULL,
+                price       DOUBLE PRECISION NOT NULL,
+                tvl         DOUBLE PRECISION NOT NULL,
+                coin_mint   TEXT NOT NULL,
+                pc_mint     TEXT NOT NULL,
+                symbol_a    TEXT NOT NULL,
+                symbol_b    TEXT NOT NULL,
+                fetched_at  TIMESTAMPTZ NOT NULL
+            )",
+        )
+        .await?;
+
+    // Paginated fetches aren't snapshot-consistent and can surface the same
+    // pool id twice; a duplicate id within one batched INSERT trips
+    // Postgres's "ON CONFLICT DO UPDATE command cannot affect row a second
+    // time". Dedupe up front, keeping the last (freshest) occurrence.
+    let mut deduped: HashMap<&str, &PoolInfoV3> = HashMap::new();
+    for p in all {
+        deduped.insert(&p.id, p);
+    }
+    let all: Vec<&PoolInfoV3> = deduped.into_values().collect();
+
+    for chunk in all.chunks(BATCH_ROWS) {
+        let mut sql = String::from(
+            "INSERT INTO pools \
+             (id, program_id, price, tvl, coin_mint, pc_mint, symbol_a, symbol_b, fetched_at) VALUES ",
+        );
+        let mut params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync>> = Vec::new();
+        for (row, p) in chunk.iter().enumerate() {
+            if row > 0 {
+                sql.push_str(", ");
+            }
+            let base = row * 8;
+            sql.push_str(&format!(
+                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, now())",
+                base + 1,
+                base + 2,
+                base + 3,
+                base + 4,
+                base + 5,
+                base + 6,
+                base + 7,
+                base + 8,
+            ));
+            let symbol_a = token_map
+                .get(&p.mint_a.address)
+                .cloned()
+                .unwrap_or_else(|| "UNKNOWN".to_string());
+            let symbol_b = token_map
+                .get(&p.mint_b.address)
+                .cloned()
+                .unwrap_or_else(|| "UNKNOWN".to_string());
+            params.push(Box::new(p.id.clone()));
+            params.push(Box::new(p.program_id.clone()));
+            params.push(Box::new(p.price));
+            params.push(Box::new(p.tvl));
+            params.push(Box::new(p.mint_a.address.clone()));
+            params.push(Box::new(p.mint_b.address.clone()));
+            params.push(Box::new(symbol_a));
+            params.push(Box::new(symbol_b));
+        }
+        sql.push_str(
+            " ON CONFLICT (id) DO UPDATE SET \
+             price = EXCLUDED.price, tvl = EXCLUDED.tvl, fetched_at = EXCLUDED.fetched_at",
+        );
+
+        let refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+            params.iter().map(|b| b.as_ref()).collect();
+        client.execute(sql.as_str(), &refs).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+// src/validate.rs
+use crate::PoolInfoV3;
+
+/// Decode `addr` with the fast base58 decoder and check it is a 32-byte pubkey.
+///
+/// `fd_bs58::decode_32` only succeeds for input that decodes to exactly 32
+/// bytes (a valid Solana pubkey), so a successful decode is itself the length
+/// check.
+fn is_pubkey(addr: &str) -> bool {
+    fd_bs58::decode_32(addr).is_ok()
+}
+
+/// A pool is valid when its `id`, `program_id` and both mint addresses all
+/// decode to well-formed 32-byte pubkeys.
+pub fn is_valid(pool: &PoolInfoV3) -> bool {
+    is_pubkey(&pool.id)
+        && is_pubkey(&pool.program_id)
+        && is_pubkey(&pool.mint_a.address)
+        && is_pubkey(&pool.mint_b.address)
+}
+
+/// Compute per-pool validity flags, logging the number of rejected records.
+///
+/// Returns a `Vec<bool>` parallel to `all` so callers can flag (via the
+/// `is_valid` Parquet column) rather than silently drop malformed pools.
+pub fn validate_pools(all: &[PoolInfoV3]) -> Vec<bool> {
+    let flags: Vec<bool> = all.iter().map(is_valid).collect();
+    let rejects = flags.iter().filter(|ok| !**ok).count();
+    if rejects > 0 {
+        eprintln!("⚠️  {rejects}/{} pools failed address validation", all.len());
+    }
+    flags
+}
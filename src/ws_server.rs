@@ -0,0 +1,202 @@
+// src/ws_server.rs
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{all_pools, geyser_source, load_token_map, PoolInfoV3};
+use reqwest::Client;
+
+/// Which feed the WebSocket server's poller task should pull pool updates
+/// from, as picked by `--source rest|grpc` in `main` (only `rest` is
+/// accepted outside server mode).
+pub enum PoolSource {
+    /// Paginate the REST API on a fixed `interval`, same as the one-shot path.
+    Rest { base_url: String },
+    /// Subscribe to the Yellowstone gRPC stream; it is push-based, so
+    /// `interval` is only the pause before resubscribing after the stream
+    /// ends or errors.
+    Grpc {
+        endpoint: String,
+        x_token: Option<String>,
+    },
+}
+
+/// A single pool snapshot as pushed to WebSocket subscribers.
+#[derive(Clone)]
+pub struct PoolUpdate {
+    pub id: String,
+    pub price: f64,
+    pub tvl: f64,
+    pub symbol_a: String,
+    pub symbol_b: String,
+}
+
+// Hand-rolled so the wire shape is explicit and stays stable even if the
+// internal struct grows extra bookkeeping fields later.
+impl Serialize for PoolUpdate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("PoolUpdate", 5)?;
+        s.serialize_field("id", &self.id)?;
+        s.serialize_field("price", &self.price)?;
+        s.serialize_field("tvl", &self.tvl)?;
+        s.serialize_field("symbol_a", &self.symbol_a)?;
+        s.serialize_field("symbol_b", &self.symbol_b)?;
+        s.end()
+    }
+}
+
+/// Per-client subscription filter, sent by the client as a JSON text
+/// message; each later filter message replaces the previous one. Both fields
+/// are optional: omit them to receive every pool.
+#[derive(serde::Deserialize, Default, Clone)]
+struct Filter {
+    #[serde(default)]
+    ids: Vec<String>,
+    #[serde(default)]
+    min_tvl: Option<f64>,
+}
+
+impl Filter {
+    fn matches(&self, u: &PoolUpdate) -> bool {
+        if let Some(min) = self.min_tvl {
+            if u.tvl < min {
+                return false;
+            }
+        }
+        if !self.ids.is_empty() && !self.ids.iter().any(|id| id == &u.id) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Run the streaming server: feed `source` and broadcast the resulting
+/// updates to every connected WebSocket client, honouring each client's
+/// subscription filter.
+pub async fn serve(addr: &str, source: PoolSource, interval: Duration) -> Result<()> {
+    let (tx, _rx) = broadcast::channel::<PoolUpdate>(4096);
+
+    // Poller task: fetch pages from whichever source was selected and fan
+    // each pool out.
+    let poll_tx = tx.clone();
+    tokio::spawn(async move {
+        let client = Client::new();
+        let token_map = load_token_map(&client).await.unwrap_or_default();
+        match source {
+            PoolSource::Rest { base_url } => loop {
+                let mut pages = all_pools(base_url.clone());
+                while let Some(page) = pages.next().await {
+                    let page = match page {
+                        Ok(p) => p,
+                        Err(e) => {
+                            eprintln!("poll error: {e}");
+                            break;
+                        }
+                    };
+                    for p in &page {
+                        let _ = poll_tx.send(to_update(p, &token_map));
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            },
+            PoolSource::Grpc { endpoint, x_token } => loop {
+                let mut pages = geyser_source::all_pools_grpc(endpoint.clone(), x_token.clone());
+                while let Some(page) = pages.next().await {
+                    let page = match page {
+                        Ok(p) => p,
+                        Err(e) => {
+                            eprintln!("grpc stream error: {e}");
+                            break;
+                        }
+                    };
+                    for p in &page {
+                        let _ = poll_tx.send(to_update(p, &token_map));
+                    }
+                }
+                // The subscription ended or errored; reconnect after a pause
+                // rather than spinning.
+                tokio::time::sleep(interval).await;
+            },
+        }
+    });
+
+    let listener = TcpListener::bind(addr).await?;
+    println!("ws server listening on {addr}");
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let rx = tx.subscribe();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, rx).await {
+                eprintln!("client {peer} dropped: {e}");
+            }
+        });
+    }
+}
+
+fn to_update(p: &PoolInfoV3, token_map: &HashMap<String, String>) -> PoolUpdate {
+    let symbol = |addr: &str| {
+        token_map
+            .get(addr)
+            .cloned()
+            .unwrap_or_else(|| "UNKNOWN".to_string())
+    };
+    PoolUpdate {
+        id: p.id.clone(),
+        price: p.price,
+        tvl: p.tvl,
+        symbol_a: symbol(&p.mint_a.address),
+        symbol_b: symbol(&p.mint_b.address),
+    }
+}
+
+async fn handle_client(
+    stream: tokio::net::TcpStream,
+    mut rx: broadcast::Receiver<PoolUpdate>,
+) -> Result<()> {
+    let ws = tokio_tungstenite::accept_async(stream).await?;
+    let (mut sink, mut source) = ws.split();
+
+    // The subscription filter is optional: clients that never send one get
+    // every pool. We can't block on `source.next()` waiting for it, since
+    // the common case is a client that only listens — so the filter update
+    // and the broadcast loop race in the same `select!`.
+    let mut filter = Filter::default();
+    loop {
+        tokio::select! {
+            msg = source.next() => {
+                match msg {
+                    Some(Ok(Message::Text(txt))) => {
+                        if let Ok(f) = serde_json::from_str::<Filter>(&txt) {
+                            filter = f;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                }
+            }
+            update = rx.recv() => {
+                match update {
+                    Ok(update) => {
+                        if filter.matches(&update) {
+                            let json = serde_json::to_string(&update)?;
+                            sink.send(Message::Text(json)).await?;
+                        }
+                    }
+                    // Lagged: the client is slower than the feed; skip ahead
+                    // rather than tearing the connection down.
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+    Ok(())
+}